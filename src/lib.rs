@@ -1,24 +1,40 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+// `num_traits`'s `Float` impl for `f32`/`f64` is routed through `libm` when
+// the `std` feature is disabled, which is what lets this crate build under
+// `no_std` (Cargo.toml enables `num-traits`'s `libm` feature in that case).
+extern crate num_traits;
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
 #[macro_use]
 #[cfg(test)]
 extern crate approx;
 
+use num_traits::Float;
 
-fn compute_dx(x_discrete:usize, x_min:f64, x_max:f64)->f64{
-    (x_max-x_min)/((x_discrete as f64)-1.0) 
+fn compute_dx<T:Float>(x_discrete:usize, x_min:T, x_max:T)->T{
+    (x_max-x_min)/(T::from(x_discrete).unwrap()-T::one())
 }
 
-fn compute_x(index:usize, dx:f64, x_min:f64)-> f64 {
-    x_min+(index as f64)*dx
+fn compute_x<T:Float>(index:usize, dx:T, x_min:T)-> T {
+    x_min+T::from(index).unwrap()*dx
 }
 
-fn thomas_algorithm<'a, Id>(
+fn thomas_algorithm<T, Id>(
     diag:Id
-)->Vec<f64> 
-where 
-    Id:Iterator<Item=(Option<f64>, f64, Option<f64>, f64)>, //lower, main, upper, solution
+)->Vec<T>
+where
+    T:Float,
+    Id:Iterator<Item=(Option<T>, T, Option<T>, T)>, //lower, main, upper, solution
 {
-    let mut upper_v:Vec<f64>=vec![];
-    let mut solve_v:Vec<f64>=vec![];
+    let mut upper_v:Vec<T>=vec![];
+    let mut solve_v:Vec<T>=vec![];
     for (index, (lower, main, upper, sol)) in diag.enumerate(){
         if lower.is_some()  {
             let upper_v_prev=upper_v[index-1];
@@ -29,47 +45,292 @@ where
             solve_v.push((sol-lower.unwrap()*solve_v_prev)/(main-upper_v_prev*lower.unwrap()))
         }
         else if lower.is_none(){
-            upper_v.push(upper.unwrap()/main);
+            if let Some(upper_v_this)=upper {
+                upper_v.push(upper_v_this/main);
+            }
             solve_v.push(sol/main);
         }
     }
 
     for (index, cprime) in upper_v.iter().enumerate().rev(){
-        solve_v[index]=solve_v[index]-cprime*solve_v[index+1];
+        solve_v[index]=solve_v[index]-*cprime*solve_v[index+1];
+    }
+    solve_v
+}
+
+/// The reason a [`thomas_algorithm_block`] (or [`solve_ode_system`]) call
+/// could not be completed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OdeSystemError {
+    /// The pivot block at this node index (0-based, within the reduced
+    /// system passed to the solver) had a zero entry on its diagonal
+    /// during LU factorization, so the block tridiagonal system is
+    /// singular or the equations are not ordered to avoid a zero pivot.
+    SingularPivot{node_index:usize},
+}
+
+impl core::fmt::Display for OdeSystemError {
+    fn fmt(&self, f:&mut core::fmt::Formatter<'_>)->core::fmt::Result{
+        match self {
+            OdeSystemError::SingularPivot{node_index}=>write!(f, "singular pivot block at node {}", node_index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OdeSystemError {}
+
+fn mat_scale<T:Float>(a:&[Vec<T>], s:T)->Vec<Vec<T>>{
+    a.iter().map(|row|row.iter().map(|v|*v*s).collect()).collect()
+}
+
+fn mat_add<T:Float>(a:&[Vec<T>], b:&[Vec<T>])->Vec<Vec<T>>{
+    a.iter().zip(b.iter()).map(|(ra, rb)|ra.iter().zip(rb.iter()).map(|(x, y)|*x+*y).collect()).collect()
+}
+
+fn mat_sub<T:Float>(a:&[Vec<T>], b:&[Vec<T>])->Vec<Vec<T>>{
+    a.iter().zip(b.iter()).map(|(ra, rb)|ra.iter().zip(rb.iter()).map(|(x, y)|*x-*y).collect()).collect()
+}
+
+fn mat_mul<T:Float>(a:&[Vec<T>], b:&[Vec<T>])->Vec<Vec<T>>{
+    let n=b[0].len();
+    let k_dim=b.len();
+    a.iter().map(|row|{
+        (0..n).map(|j|{
+            (0..k_dim).fold(T::zero(), |acc, k|acc+row[k]*b[k][j])
+        }).collect()
+    }).collect()
+}
+
+fn mat_mul_vec<T:Float>(a:&[Vec<T>], v:&[T])->Vec<T>{
+    a.iter().map(|row|row.iter().zip(v.iter()).fold(T::zero(), |acc, (x, y)|acc+*x**y)).collect()
+}
+
+fn vec_sub<T:Float>(a:&[T], b:&[T])->Vec<T>{
+    a.iter().zip(b.iter()).map(|(x, y)|*x-*y).collect()
+}
+
+/// An LU factorization (no pivoting) of a single `m*m` pivot block, kept
+/// around just long enough to solve against both the block super-diagonal
+/// and the right-hand side at that node.
+struct LuDecomp<T>{
+    lu:Vec<Vec<T>>,
+    size:usize,
+}
+
+fn lu_decompose<T:Float>(a:&[Vec<T>], node_index:usize)->Result<LuDecomp<T>, OdeSystemError>{
+    let m=a.len();
+    let mut lu:Vec<Vec<T>>=a.iter().map(|row|row.to_vec()).collect();
+    for k in 0..m {
+        if lu[k][k]==T::zero() {
+            return Err(OdeSystemError::SingularPivot{node_index});
+        }
+        let (pivot_rows, rest_rows)=lu.split_at_mut(k+1);
+        let pivot_row=&pivot_rows[k];
+        for row in rest_rows.iter_mut() {
+            let factor=row[k]/pivot_row[k];
+            row[k]=factor;
+            for (entry, pivot_entry) in row[(k+1)..].iter_mut().zip(pivot_row[(k+1)..].iter()) {
+                *entry=*entry-factor**pivot_entry;
+            }
+        }
+    }
+    Ok(LuDecomp{lu, size:m})
+}
+
+impl<T:Float> LuDecomp<T> {
+    fn solve_vec(&self, b:&[T])->Vec<T>{
+        let m=self.size;
+        let mut y=vec![T::zero(); m];
+        for i in 0..m {
+            let sum=(0..i).fold(b[i], |acc, k|acc-self.lu[i][k]*y[k]);
+            y[i]=sum;
+        }
+        let mut x=vec![T::zero(); m];
+        for i in (0..m).rev() {
+            let sum=((i+1)..m).fold(y[i], |acc, k|acc-self.lu[i][k]*x[k]);
+            x[i]=sum/self.lu[i][i];
+        }
+        x
+    }
+
+    fn solve_mat(&self, b:&[Vec<T>])->Vec<Vec<T>>{
+        let m=self.size;
+        let mut result=vec![vec![T::zero(); m]; m];
+        for col in 0..m {
+            let b_col:Vec<T>=(0..m).map(|row|b[row][col]).collect();
+            let x_col=self.solve_vec(&b_col);
+            for (row, value) in x_col.into_iter().enumerate() {
+                result[row][col]=value;
+            }
+        }
+        result
+    }
+}
+
+/// The block-tridiagonal analogue of [`thomas_algorithm`]: each lower,
+/// main and upper entry is a small `m*m` matrix and each solution entry
+/// is an `m`-vector, for solving systems of `m` coupled equations. The
+/// forward sweep factors each pivot block `Main-Lower*UpperPrime_prev`
+/// via LU decomposition (instead of the scalar division used by the
+/// unblocked algorithm) and the back-substitution replaces scalar
+/// products with matrix-vector products.
+fn thomas_algorithm_block<T, Id>(
+    diag:Id
+)->Result<Vec<Vec<T>>, OdeSystemError>
+where
+    T:Float,
+    Id:Iterator<Item=(Option<Vec<Vec<T>>>, Vec<Vec<T>>, Option<Vec<Vec<T>>>, Vec<T>)>,
+{
+    let mut upper_prime_v:Vec<Vec<Vec<T>>>=vec![];
+    let mut solve_v:Vec<Vec<T>>=vec![];
+    for (index, (lower, main, upper, sol)) in diag.enumerate(){
+        let pivot=match &lower {
+            Some(lower_block)=>mat_sub(&main, &mat_mul(lower_block, &upper_prime_v[index-1])),
+            None=>main,
+        };
+        let lu=lu_decompose(&pivot, index)?;
+        if let Some(upper_block)=&upper {
+            upper_prime_v.push(lu.solve_mat(upper_block));
+        }
+        let rhs=match &lower {
+            Some(lower_block)=>vec_sub(&sol, &mat_mul_vec(lower_block, &solve_v[index-1])),
+            None=>sol,
+        };
+        solve_v.push(lu.solve_vec(&rhs));
+    }
+
+    for (index, upper_prime) in upper_prime_v.iter().enumerate().rev(){
+        let correction=mat_mul_vec(upper_prime, &solve_v[index+1]);
+        solve_v[index]=vec_sub(&solve_v[index], &correction);
+    }
+    Ok(solve_v)
+}
+
+/// A boundary condition applied at one end of the solution domain.
+///
+/// `Dirichlet` fixes the value of `f` itself, `Neumann` fixes `f'`, and
+/// `Robin` fixes the linear combination `a*f+b*f'`. `Neumann(value)` is
+/// equivalent to `Robin{a:0.0, b:1.0, value}`.
+#[derive(Debug, Clone, Copy)]
+pub enum BoundaryCondition<T> {
+    Dirichlet(T),
+    Neumann(T),
+    Robin{a:T, b:T, value:T},
+}
+
+/// The result of [`solve_ode`]: the solution samples on the internal
+/// uniform grid (spanning `x_min` to `x_max` inclusive of both boundary
+/// values) plus enough information to query it off-grid.
+#[derive(Debug, Clone)]
+pub struct OdeSolution<T> {
+    x_min:T,
+    x_max:T,
+    dx:T,
+    ys:Vec<T>,
+}
+
+impl<T:Float> OdeSolution<T> {
+    /// Evaluates the solution at an arbitrary `x` in `[x_min, x_max]` via
+    /// cubic-Hermite interpolation between the two nearest grid nodes,
+    /// using centered (one-sided at the boundaries) finite-difference
+    /// slope estimates. `x` outside the domain is clamped to the nearest
+    /// endpoint.
+    pub fn eval(&self, x:T)->T{
+        let last=self.ys.len()-1;
+        if x<=self.x_min {
+            return self.ys[0];
+        }
+        if x>=self.x_max {
+            return self.ys[last];
+        }
+        let t=(x-self.x_min)/self.dx;
+        let i=(t.floor().to_usize().unwrap()).min(last-1);
+        let x0=compute_x(i, self.dx, self.x_min);
+        let local_t=(x-x0)/self.dx;
+        let y0=self.ys[i];
+        let y1=self.ys[i+1];
+        let m0=self.slope(i);
+        let m1=self.slope(i+1);
+        let two=T::from(2.0).unwrap();
+        let three=T::from(3.0).unwrap();
+        let t2=local_t*local_t;
+        let t3=t2*local_t;
+        let h00=two*t3-three*t2+T::one();
+        let h10=t3-two*t2+local_t;
+        let h01=-two*t3+three*t2;
+        let h11=t3-t2;
+        h00*y0+h10*self.dx*m0+h01*y1+h11*self.dx*m1
+    }
+
+    fn slope(&self, index:usize)->T{
+        let last=self.ys.len()-1;
+        let two=T::from(2.0).unwrap();
+        if index==0 {
+            (self.ys[1]-self.ys[0])/self.dx
+        }
+        else if index==last {
+            (self.ys[last]-self.ys[last-1])/self.dx
+        }
+        else {
+            (self.ys[index+1]-self.ys[index-1])/(two*self.dx)
+        }
+    }
+
+    /// The grid points the solution was sampled at, including both endpoints.
+    pub fn xs<'a>(&'a self)->impl Iterator<Item=T> + 'a {
+        let dx=self.dx;
+        let x_min=self.x_min;
+        (0..self.ys.len()).map(move |index|compute_x(index, dx, x_min))
+    }
+
+    /// The solution samples in grid order, including both endpoints.
+    pub fn ys(&self)->core::slice::Iter<'_, T>{
+        self.ys.iter()
+    }
+
+    /// The raw sample vector, kept for callers that previously consumed
+    /// `solve_ode`'s bare `Vec<T>` directly.
+    pub fn values(&self)->&Vec<T>{
+        &self.ys
     }
-    solve_v  
 }
 
-/// Solves ODEs of the form fn2(x)*f''(x)+fn1(x)*f'(x)+fn*f(x)=0
+/// Solves ODEs of the form fn2(x)*f''(x)+fn1(x)*f'(x)+fn*f(x)=g(x)
 /// # Examples
 /// ```
+/// use second_order_ode::BoundaryCondition;
 /// let fn2=|_|1.5;
 /// let fn1=|_|5.0;
 /// let fnc=|_|1.5;
-/// let init_cond_lower=0.0;
-/// let init_cond_upper=1.0;
+/// let g=|_|0.0;
+/// let lower_condition=BoundaryCondition::Dirichlet(0.0);
+/// let upper_condition=BoundaryCondition::Dirichlet(1.0);
 /// let x_min=0.0;
 /// let x_max=1.0;
 /// let n=100;
 /// let result=second_order_ode::solve_ode(
-///     &fn2, &fn1, 
-///     &fnc, init_cond_lower, init_cond_upper, 
+///     &fn2, &fn1,
+///     &fnc, &g, lower_condition, upper_condition,
 ///     x_min, x_max, n
 /// );
+/// let midpoint=result.eval(0.5);
 /// ```
-pub fn solve_ode(
-    second_deriv_coef:&Fn(f64)->f64,
-    first_deriv_coef:&Fn(f64)->f64,
-    fn_coef:&Fn(f64)->f64,
-    initial_condition_lower:f64,
-    initial_condition_upper:f64,
-    x_min:f64,
-    x_max:f64,
+pub fn solve_ode<T:Float>(
+    second_deriv_coef:&Fn(T)->T,
+    first_deriv_coef:&Fn(T)->T,
+    fn_coef:&Fn(T)->T,
+    source_coef:&Fn(T)->T,
+    lower_condition:BoundaryCondition<T>,
+    upper_condition:BoundaryCondition<T>,
+    x_min:T,
+    x_max:T,
     num_steps:usize
-)->Vec<f64>{
+)->OdeSolution<T>{
+    let two=T::from(2.0).unwrap();
     let dx=compute_dx(num_steps+2, x_min, x_max);
     let dx_sq=dx.powi(2);
-    let dx2=dx*2.0;
+    let dx2=dx*two;
     let get_upper_coef=|index:usize|{
         let x=compute_x(index, dx, x_min);
         second_deriv_coef(x)/dx_sq+first_deriv_coef(x)/dx2
@@ -80,84 +341,786 @@ pub fn solve_ode(
     };
     let get_main_coef=|index:usize|{
         let x=compute_x(index, dx, x_min);
-        fn_coef(x)-second_deriv_coef(x)*2.0/dx_sq
+        fn_coef(x)-second_deriv_coef(x)*two/dx_sq
+    };
+    let get_derivative_coef=|condition:BoundaryCondition<T>|{
+        match condition {
+            BoundaryCondition::Neumann(value)=>(T::zero(), T::one(), value),
+            BoundaryCondition::Robin{a, b, value}=>(a, b, value),
+            BoundaryCondition::Dirichlet(_)=>unreachable!("Dirichlet boundaries do not promote a node to an unknown"),
+        }
     };
-    let diag=(1..num_steps+1).map(|index|{
-        if index==1 {
+    let lower_is_dirichlet=matches!(lower_condition, BoundaryCondition::Dirichlet(_));
+    let upper_is_dirichlet=matches!(upper_condition, BoundaryCondition::Dirichlet(_));
+    let start_index=if lower_is_dirichlet {1} else {0};
+    let end_index=if upper_is_dirichlet {num_steps} else {num_steps+1};
+    let diag=(start_index..=end_index).map(|index|{
+        if index==start_index && index==end_index {
+            // A single unknown node: it has no lower or upper neighbor
+            // in the reduced system, so both boundaries' contributions
+            // are folded into this one row instead of being split
+            // across two.
+            let x=compute_x(index, dx, x_min);
+            if lower_is_dirichlet && upper_is_dirichlet {
+                let initial_condition_lower=match lower_condition { BoundaryCondition::Dirichlet(v)=>v, _=>unreachable!() };
+                let initial_condition_upper=match upper_condition { BoundaryCondition::Dirichlet(v)=>v, _=>unreachable!() };
+                (
+                    None,
+                    get_main_coef(index),
+                    None,
+                    source_coef(x)-initial_condition_lower*get_lower_coef(index)-initial_condition_upper*get_upper_coef(index)
+                )
+            }
+            else if lower_is_dirichlet {
+                // The upper boundary is the derivative condition; its
+                // lower neighbor (index-1) is the known Dirichlet value,
+                // not an unknown row, so it's folded into the rhs.
+                let initial_condition_lower=match lower_condition { BoundaryCondition::Dirichlet(v)=>v, _=>unreachable!() };
+                let (a, b, value)=get_derivative_coef(upper_condition);
+                (None, a+b/dx, None, value+(b/dx)*initial_condition_lower)
+            }
+            else {
+                // upper_is_dirichlet: the lower boundary is the
+                // derivative condition; its upper neighbor (index+1) is
+                // the known Dirichlet value, not an unknown row.
+                let initial_condition_upper=match upper_condition { BoundaryCondition::Dirichlet(v)=>v, _=>unreachable!() };
+                let (a, b, value)=get_derivative_coef(lower_condition);
+                (None, a-b/dx, None, value-(b/dx)*initial_condition_upper)
+            }
+        }
+        else if index==start_index && !lower_is_dirichlet {
+            let (a, b, value)=get_derivative_coef(lower_condition);
+            (None, a-b/dx, Some(b/dx), value)
+        }
+        else if index==end_index && !upper_is_dirichlet {
+            let (a, b, value)=get_derivative_coef(upper_condition);
+            (Some(-b/dx), a+b/dx, None, value)
+        }
+        else if index==1 && lower_is_dirichlet {
+            let initial_condition_lower=match lower_condition { BoundaryCondition::Dirichlet(v)=>v, _=>unreachable!() };
+            let x=compute_x(index, dx, x_min);
             (
-                None, 
-                get_main_coef(index), 
-                Some(get_upper_coef(index+1)), 
-                -initial_condition_lower*get_lower_coef(index)
+                None,
+                get_main_coef(index),
+                Some(get_upper_coef(index+1)),
+                source_coef(x)-initial_condition_lower*get_lower_coef(index)
             )
         }
-        else if index==num_steps {
+        else if index==num_steps && upper_is_dirichlet {
+            let initial_condition_upper=match upper_condition { BoundaryCondition::Dirichlet(v)=>v, _=>unreachable!() };
+            let x=compute_x(index, dx, x_min);
             (
-                Some(get_lower_coef(index-1)), 
-                get_main_coef(index), 
-                None, 
-                -initial_condition_upper*get_upper_coef(index)
+                Some(get_lower_coef(index-1)),
+                get_main_coef(index),
+                None,
+                source_coef(x)-initial_condition_upper*get_upper_coef(index)
             )
         }
         else {
+            let x=compute_x(index, dx, x_min);
             (
-                Some(get_lower_coef(index-1)), 
-                get_main_coef(index), 
+                Some(get_lower_coef(index-1)),
+                get_main_coef(index),
                 Some(get_upper_coef(index+1)),
-                0.0
+                source_coef(x)
             )
         }
     });
-    thomas_algorithm(diag)
+    let raw=thomas_algorithm(diag);
+    let mut ys:Vec<T>=vec![T::zero(); num_steps+2];
+    for (offset, value) in raw.into_iter().enumerate(){
+        ys[start_index+offset]=value;
+    }
+    if lower_is_dirichlet {
+        ys[0]=match lower_condition { BoundaryCondition::Dirichlet(v)=>v, _=>unreachable!() };
+    }
+    if upper_is_dirichlet {
+        ys[num_steps+1]=match upper_condition { BoundaryCondition::Dirichlet(v)=>v, _=>unreachable!() };
+    }
+    OdeSolution { x_min, x_max, dx, ys }
+}
 
+/// Solves systems of `m` coupled ODEs of the form
+/// `A(x)*f''(x)+B(x)*f'(x)+C(x)*f(x)=g(x)`, where `A`, `B` and `C` are
+/// `m*m` matrix-valued coefficients and `f`, `g` are `m`-vector valued,
+/// with fixed (Dirichlet) values at both ends.
+///
+/// Returns one `m`-vector per interior grid point (excluding the
+/// boundary nodes, which are exactly `initial_condition_lower` and
+/// `initial_condition_upper`), or an error if a pivot block in the
+/// underlying block Thomas algorithm is singular.
+/// # Examples
+/// ```
+/// let a=|_:f64|vec![vec![1.5, 0.0], vec![0.0, 1.5]];
+/// let b=|_:f64|vec![vec![5.0, 0.0], vec![0.0, 5.0]];
+/// let c=|_:f64|vec![vec![1.5, 0.0], vec![0.0, 1.5]];
+/// let g=|_:f64|vec![0.0, 0.0];
+/// let result=second_order_ode::solve_ode_system(
+///     &a, &b, &c, &g,
+///     vec![0.0, 0.0], vec![1.0, 1.0],
+///     0.0, 1.0, 100
+/// ).unwrap();
+/// ```
+pub fn solve_ode_system<T:Float>(
+    second_deriv_coef:&Fn(T)->Vec<Vec<T>>,
+    first_deriv_coef:&Fn(T)->Vec<Vec<T>>,
+    fn_coef:&Fn(T)->Vec<Vec<T>>,
+    source_coef:&Fn(T)->Vec<T>,
+    initial_condition_lower:Vec<T>,
+    initial_condition_upper:Vec<T>,
+    x_min:T,
+    x_max:T,
+    num_steps:usize
+)->Result<Vec<Vec<T>>, OdeSystemError>{
+    let two=T::from(2.0).unwrap();
+    let dx=compute_dx(num_steps+2, x_min, x_max);
+    let dx_sq=dx.powi(2);
+    let dx2=dx*two;
+    let get_upper_coef=|index:usize|{
+        let x=compute_x(index, dx, x_min);
+        mat_add(&mat_scale(&second_deriv_coef(x), T::one()/dx_sq), &mat_scale(&first_deriv_coef(x), T::one()/dx2))
+    };
+    let get_lower_coef=|index:usize|{
+        let x=compute_x(index, dx, x_min);
+        mat_sub(&mat_scale(&second_deriv_coef(x), T::one()/dx_sq), &mat_scale(&first_deriv_coef(x), T::one()/dx2))
+    };
+    let get_main_coef=|index:usize|{
+        let x=compute_x(index, dx, x_min);
+        mat_sub(&fn_coef(x), &mat_scale(&second_deriv_coef(x), two/dx_sq))
+    };
+    let diag=(1..=num_steps).map(|index|{
+        if index==1 && index==num_steps {
+            // A single unknown node: it has no lower or upper neighbor
+            // in the reduced system, so both boundaries' contributions
+            // are subtracted from the same row.
+            let x=compute_x(index, dx, x_min);
+            let lower_coef=get_lower_coef(index);
+            let upper_coef=get_upper_coef(index);
+            let rhs=vec_sub(
+                &vec_sub(&source_coef(x), &mat_mul_vec(&lower_coef, &initial_condition_lower)),
+                &mat_mul_vec(&upper_coef, &initial_condition_upper)
+            );
+            (None, get_main_coef(index), None, rhs)
+        }
+        else if index==1 {
+            let x=compute_x(index, dx, x_min);
+            let lower_coef=get_lower_coef(index);
+            let rhs=vec_sub(&source_coef(x), &mat_mul_vec(&lower_coef, &initial_condition_lower));
+            (None, get_main_coef(index), Some(get_upper_coef(index+1)), rhs)
+        }
+        else if index==num_steps {
+            let x=compute_x(index, dx, x_min);
+            let upper_coef=get_upper_coef(index);
+            let rhs=vec_sub(&source_coef(x), &mat_mul_vec(&upper_coef, &initial_condition_upper));
+            (Some(get_lower_coef(index-1)), get_main_coef(index), None, rhs)
+        }
+        else {
+            let x=compute_x(index, dx, x_min);
+            (Some(get_lower_coef(index-1)), get_main_coef(index), Some(get_upper_coef(index+1)), source_coef(x))
+        }
+    });
+    thomas_algorithm_block(diag)
+}
+
+// Dormand-Prince RK45 Butcher tableau.
+const DP_C:[f64; 7]=[0.0, 1.0/5.0, 3.0/10.0, 4.0/5.0, 8.0/9.0, 1.0, 1.0];
+const DP_A21:f64=1.0/5.0;
+const DP_A31:f64=3.0/40.0;
+const DP_A32:f64=9.0/40.0;
+const DP_A41:f64=44.0/45.0;
+const DP_A42:f64=-56.0/15.0;
+const DP_A43:f64=32.0/9.0;
+const DP_A51:f64=19372.0/6561.0;
+const DP_A52:f64=-25360.0/2187.0;
+const DP_A53:f64=64448.0/6561.0;
+const DP_A54:f64=-212.0/729.0;
+const DP_A61:f64=9017.0/3168.0;
+const DP_A62:f64=-355.0/33.0;
+const DP_A63:f64=46732.0/5247.0;
+const DP_A64:f64=49.0/176.0;
+const DP_A65:f64=-5103.0/18656.0;
+const DP_B5:[f64; 6]=[35.0/384.0, 0.0, 500.0/1113.0, 125.0/192.0, -2187.0/6784.0, 11.0/84.0];
+const DP_B4:[f64; 6]=[5179.0/57600.0, 0.0, 7571.0/16695.0, 393.0/640.0, -92097.0/339200.0, 187.0/2100.0];
+
+const RK45_SAFETY:f64=0.9;
+const RK45_FACMIN:f64=0.2;
+const RK45_FACMAX:f64=5.0;
+
+fn t<T:Float>(value:f64)->T{
+    T::from(value).unwrap()
+}
+
+/// Evaluates `u' = [u1, (g(x)-fn1(x)*u1-fn(x)*u0)/fn2(x)]`, the first-order
+/// system equivalent to `fn2(x)*f''+fn1(x)*f'+fn(x)*f=g(x)` with `u=[f,f']`.
+fn ivp_deriv<T:Float>(
+    second_deriv_coef:&Fn(T)->T,
+    first_deriv_coef:&Fn(T)->T,
+    fn_coef:&Fn(T)->T,
+    source_coef:&Fn(T)->T,
+    x:T,
+    u:[T; 2]
+)->[T; 2]{
+    [
+        u[1],
+        (source_coef(x)-first_deriv_coef(x)*u[1]-fn_coef(x)*u[0])/second_deriv_coef(x)
+    ]
+}
+
+/// Takes a single Dormand-Prince RK45 step from `(x,u)` with step size `h`,
+/// returning the 5th-order and embedded 4th-order solution estimates.
+fn rk45_step<T:Float>(
+    second_deriv_coef:&Fn(T)->T,
+    first_deriv_coef:&Fn(T)->T,
+    fn_coef:&Fn(T)->T,
+    source_coef:&Fn(T)->T,
+    x:T,
+    u:[T; 2],
+    h:T
+)->([T; 2], [T; 2]){
+    let deriv=|x:T, u:[T; 2]|ivp_deriv(second_deriv_coef, first_deriv_coef, fn_coef, source_coef, x, u);
+    let k1=deriv(x, u);
+    let k2=deriv(x+t::<T>(DP_C[1])*h, [
+        u[0]+h*t::<T>(DP_A21)*k1[0],
+        u[1]+h*t::<T>(DP_A21)*k1[1]
+    ]);
+    let k3=deriv(x+t::<T>(DP_C[2])*h, [
+        u[0]+h*(t::<T>(DP_A31)*k1[0]+t::<T>(DP_A32)*k2[0]),
+        u[1]+h*(t::<T>(DP_A31)*k1[1]+t::<T>(DP_A32)*k2[1])
+    ]);
+    let k4=deriv(x+t::<T>(DP_C[3])*h, [
+        u[0]+h*(t::<T>(DP_A41)*k1[0]+t::<T>(DP_A42)*k2[0]+t::<T>(DP_A43)*k3[0]),
+        u[1]+h*(t::<T>(DP_A41)*k1[1]+t::<T>(DP_A42)*k2[1]+t::<T>(DP_A43)*k3[1])
+    ]);
+    let k5=deriv(x+t::<T>(DP_C[4])*h, [
+        u[0]+h*(t::<T>(DP_A51)*k1[0]+t::<T>(DP_A52)*k2[0]+t::<T>(DP_A53)*k3[0]+t::<T>(DP_A54)*k4[0]),
+        u[1]+h*(t::<T>(DP_A51)*k1[1]+t::<T>(DP_A52)*k2[1]+t::<T>(DP_A53)*k3[1]+t::<T>(DP_A54)*k4[1])
+    ]);
+    let k6=deriv(x+t::<T>(DP_C[5])*h, [
+        u[0]+h*(t::<T>(DP_A61)*k1[0]+t::<T>(DP_A62)*k2[0]+t::<T>(DP_A63)*k3[0]+t::<T>(DP_A64)*k4[0]+t::<T>(DP_A65)*k5[0]),
+        u[1]+h*(t::<T>(DP_A61)*k1[1]+t::<T>(DP_A62)*k2[1]+t::<T>(DP_A63)*k3[1]+t::<T>(DP_A64)*k4[1]+t::<T>(DP_A65)*k5[1])
+    ]);
+    let k=[k1, k2, k3, k4, k5, k6];
+    let mut y5=u;
+    let mut y4=u;
+    for i in 0..2 {
+        y5[i]=y5[i]+h*(t::<T>(DP_B5[0])*k[0][i]+t::<T>(DP_B5[1])*k[1][i]+t::<T>(DP_B5[2])*k[2][i]+t::<T>(DP_B5[3])*k[3][i]+t::<T>(DP_B5[4])*k[4][i]+t::<T>(DP_B5[5])*k[5][i]);
+        y4[i]=y4[i]+h*(t::<T>(DP_B4[0])*k[0][i]+t::<T>(DP_B4[1])*k[1][i]+t::<T>(DP_B4[2])*k[2][i]+t::<T>(DP_B4[3])*k[3][i]+t::<T>(DP_B4[4])*k[4][i]+t::<T>(DP_B4[5])*k[5][i]);
+    }
+    (y5, y4)
+}
+
+/// The embedded-pair error estimate driving [`solve_ivp`]'s step-size
+/// control, scaled by `atol`/`rtol` per component.
+///
+/// A component's scale (`atol+rtol*|y5[i]|`) can be exactly zero (e.g.
+/// `atol=0.0` against a transiently-zero solution component); only an
+/// exactly-matching embedded pair (`diff==0`) is a genuine zero error in
+/// that case, anything else is an unscaled miss. Likewise a NaN `diff`
+/// (`Inf-Inf` from a genuinely diverging solution) is a real blow-up, not
+/// a degenerate tolerance, so it is reported as unbounded error rather
+/// than masquerading as an exact step.
+fn rk45_error<T:Float>(y5:[T; 2], y4:[T; 2], atol:T, rtol:T)->T{
+    let two=t::<T>(2.0);
+    let squared_error_sum=(0..2).map(|i|{
+        let diff=y5[i]-y4[i];
+        let scale=atol+rtol*y5[i].abs();
+        if diff.is_nan() {
+            T::infinity()
+        }
+        else if scale==T::zero() {
+            if diff==T::zero() { T::zero() } else { T::infinity() }
+        }
+        else {
+            (diff/scale).powi(2)
+        }
+    }).fold(T::zero(), |acc, v|acc+v);
+    (squared_error_sum/two).sqrt()
+}
+
+/// Integrates `fn2(x)*f''(x)+fn1(x)*f'(x)+fn(x)*f(x)=g(x)` forward from
+/// `f(x_min)=f_init`, `f'(x_min)=df_init` using an embedded adaptive
+/// Runge-Kutta method (Dormand-Prince RK45), taking as few or as many
+/// steps as the local error estimate demands.
+///
+/// Returns the accepted `(x, f, f')` samples, including the initial point.
+/// The step size is bounded below by a small fraction of `x_max-x_min`;
+/// if a degenerate tolerance (e.g. `atol=0.0` alongside a transiently
+/// zero solution component) would otherwise shrink it past that floor,
+/// the step is accepted even though its local error estimate exceeds
+/// `rtol`/`atol`.
+/// # Examples
+/// ```
+/// let fn2=|_|1.0;
+/// let fn1=|_|0.0;
+/// let fnc=|_|0.0;
+/// let g=|_|0.0;
+/// let result=second_order_ode::solve_ivp(
+///     &fn2, &fn1, &fnc, &g,
+///     0.0, 1.0,
+///     0.0, 1.0,
+///     0.1, 1e-6, 1e-9
+/// );
+/// ```
+pub fn solve_ivp<T:Float>(
+    second_deriv_coef:&Fn(T)->T,
+    first_deriv_coef:&Fn(T)->T,
+    fn_coef:&Fn(T)->T,
+    source_coef:&Fn(T)->T,
+    f_init:T,
+    df_init:T,
+    x_min:T,
+    x_max:T,
+    h_init:T,
+    rtol:T,
+    atol:T,
+)->Vec<(T, T, T)>{
+    let one=T::one();
+    let safety=t::<T>(RK45_SAFETY);
+    let facmin=t::<T>(RK45_FACMIN);
+    let facmax=t::<T>(RK45_FACMAX);
+    // A floor on the step size so a degenerate tolerance (e.g. `atol=0.0`
+    // paired with a solution component that is transiently zero) can't
+    // make `err` blow up and shrink `h` toward zero forever: once `h`
+    // underflows this floor the step is accepted as-is.
+    let h_min=(x_max-x_min).abs()*t::<T>(1e-12);
+    let mut x=x_min;
+    let mut u=[f_init, df_init];
+    let mut h=h_init;
+    let mut result=vec![(x, u[0], u[1])];
+    while x<x_max {
+        if x+h>x_max {
+            h=x_max-x;
+        }
+        loop {
+            let (y5, y4)=rk45_step(second_deriv_coef, first_deriv_coef, fn_coef, source_coef, x, u, h);
+            let err=rk45_error(y5, y4, atol, rtol);
+            let fac=if err==T::zero() {
+                facmax
+            }
+            else {
+                safety*(one/err).powf(one/t::<T>(5.0))
+            };
+            let h_new=h*fac.min(facmax).max(facmin);
+            if err<=one || h<=h_min {
+                x=x+h;
+                u=y5;
+                result.push((x, u[0], u[1]));
+                h=h_new.max(h_min);
+                break;
+            }
+            else {
+                h=h_new;
+            }
+        }
+    }
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    #[test]
-    fn test_thomas_algorithm() {
-        let mut diag:Vec<(Option<f64>, f64, Option<f64>, f64)>=vec![];
-        diag.push((None, 0.3, Some(0.9), 0.3));
-        diag.push((Some(0.4), 0.4, Some(0.2), -0.5));
-        diag.push((Some(0.6), 0.2, None, 0.3));
-        let expected:Vec<f64>=vec![
-            -1.5714286,
-            0.8571429,
-            -1.0714286
+
+    fn run_test_thomas_algorithm<T:Float+core::fmt::Debug+::approx::AbsDiffEq<Epsilon=T>>(epsilon:T){
+        let mut diag:Vec<(Option<T>, T, Option<T>, T)>=vec![];
+        diag.push((None, t(0.3), Some(t(0.9)), t(0.3)));
+        diag.push((Some(t(0.4)), t(0.4), Some(t(0.2)), t(-0.5)));
+        diag.push((Some(t(0.6)), t(0.2), None, t(0.3)));
+        let expected:Vec<T>=vec![
+            t(-1.5714286),
+            t(0.8571429),
+            t(-1.0714286)
         ];
         let result=thomas_algorithm(diag.iter().map(|v|*v));
         for (res, ex) in result.iter().zip(expected.iter()){
-            assert_abs_diff_eq!(res, ex, epsilon=0.000001);
+            assert_abs_diff_eq!(res, ex, epsilon=epsilon);
         }
     }
 
     #[test]
-    fn test_solve_ode(){
-        let fn2=|_|1.5;
-        let fn1=|_|5.0;
-        let fnc=|_|1.5;
-        let init_cond_lower=0.0;
-        let init_cond_upper=1.0;
+    fn test_thomas_algorithm_f64() {
+        run_test_thomas_algorithm::<f64>(0.000001);
+    }
+
+    #[test]
+    fn test_thomas_algorithm_f32() {
+        run_test_thomas_algorithm::<f32>(0.0001);
+    }
+
+    fn run_test_solve_ode<T:Float+core::fmt::Debug+::approx::AbsDiffEq<Epsilon=T>>(epsilon:T){
+        let fn2=|_|t(1.5);
+        let fn1=|_|t(5.0);
+        let fnc=|_|t(1.5);
+        let g=|_|T::zero();
+        let lower_condition=BoundaryCondition::Dirichlet(T::zero());
+        let upper_condition=BoundaryCondition::Dirichlet(T::one());
+        let x_min=T::zero();
+        let x_max=T::one();
+        let n=100;
+        let expected_fnc=|x:T|{
+            let coef1:T=t(-1.0/3.0);
+            let coef2:T=t(-3.0);
+
+            let c2=T::one()/(coef1.exp()-coef2.exp());
+            let c1=-c2;
+            c1*(coef2*x).exp()+c2*(coef1*x).exp()
+        };
+        let result=solve_ode(
+            &fn2, &fn1,
+            &fnc, &g, lower_condition, upper_condition,
+            x_min, x_max, n
+        );
+        let dx=compute_dx(n+2, x_min, x_max);
+        for (index, res) in result.values().iter().enumerate(){
+            assert_abs_diff_eq!(*res, expected_fnc(compute_x(index, dx, x_min)), epsilon=epsilon);
+        }
+        assert_abs_diff_eq!(result.eval(t(0.5)), expected_fnc(t(0.5)), epsilon=epsilon);
+    }
+
+    #[test]
+    fn test_solve_ode_f64(){
+        run_test_solve_ode::<f64>(0.001);
+    }
+
+    #[test]
+    fn test_solve_ode_f32(){
+        run_test_solve_ode::<f32>(0.001);
+    }
+
+    #[test]
+    fn test_solve_ode_with_source(){
+        // f''(x)=1, f(0)=0, f(1)=0 => f(x)=(x^2-x)/2
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|0.0;
+        let g=|_|1.0;
+        let lower_condition=BoundaryCondition::Dirichlet(0.0);
+        let upper_condition=BoundaryCondition::Dirichlet(0.0);
         let x_min=0.0;
         let x_max=1.0;
         let n=100;
-        let expected_fnc=|x:f64|{
-            let coef1:f64=-1.0/3.0;
-            let coef2:f64=-3.0;
+        let expected_fnc=|x:f64|(x.powi(2)-x)/2.0;
+        let result=solve_ode(
+            &fn2, &fn1,
+            &fnc, &g, lower_condition, upper_condition,
+            x_min, x_max, n
+        );
+        let dx=compute_dx(n+2, x_min, x_max);
+        for (index, res) in result.values().iter().enumerate(){
+            assert_abs_diff_eq!(*res, expected_fnc(compute_x(index, dx, x_min)), epsilon=0.001);
+        }
+    }
+
+    #[test]
+    fn test_solve_ode_single_unknown_node(){
+        // f''(x)=0, f(0)=0, f(1)=1 => f(x)=x, with a single interior
+        // node (num_steps=1) that has no lower or upper neighbor at all.
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|0.0;
+        let g=|_|0.0;
+        let lower_condition=BoundaryCondition::Dirichlet(0.0);
+        let upper_condition=BoundaryCondition::Dirichlet(1.0);
+        let result=solve_ode(
+            &fn2, &fn1,
+            &fnc, &g, lower_condition, upper_condition,
+            0.0, 1.0, 1
+        );
+        for (res, expected) in result.values().iter().zip([0.0, 0.5, 1.0].iter()){
+            assert_abs_diff_eq!(*res, *expected, epsilon=1e-9);
+        }
+    }
+
+    #[test]
+    fn test_solve_ode_single_unknown_node_dirichlet_lower_neumann_upper(){
+        // f''(x)=0, f(0)=0, f'(1)=1 => f(x)=x, with num_steps=0: a single
+        // unknown node (index 1) whose only neighbor (index 0) is the
+        // known Dirichlet value, not an unknown row.
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|0.0;
+        let g=|_|0.0;
+        let lower_condition=BoundaryCondition::Dirichlet(0.0);
+        let upper_condition=BoundaryCondition::Neumann(1.0);
+        let result=solve_ode(
+            &fn2, &fn1,
+            &fnc, &g, lower_condition, upper_condition,
+            0.0, 1.0, 0
+        );
+        for (res, expected) in result.values().iter().zip([0.0, 1.0].iter()){
+            assert_abs_diff_eq!(*res, *expected, epsilon=1e-9);
+        }
+    }
+
+    #[test]
+    fn test_solve_ode_single_unknown_node_neumann_lower_dirichlet_upper(){
+        // f''(x)=0, f'(0)=1, f(1)=1 => f(x)=x, with num_steps=0: a single
+        // unknown node (index 0) whose only neighbor (index 1) is the
+        // known Dirichlet value, not an unknown row.
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|0.0;
+        let g=|_|0.0;
+        let lower_condition=BoundaryCondition::Neumann(1.0);
+        let upper_condition=BoundaryCondition::Dirichlet(1.0);
+        let result=solve_ode(
+            &fn2, &fn1,
+            &fnc, &g, lower_condition, upper_condition,
+            0.0, 1.0, 0
+        );
+        for (res, expected) in result.values().iter().zip([0.0, 1.0].iter()){
+            assert_abs_diff_eq!(*res, *expected, epsilon=1e-9);
+        }
+    }
+
+    #[test]
+    fn test_solve_ode_with_neumann_lower(){
+        // f''(x)=0, f'(0)=1, f(1)=0 => f(x)=x-1
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|0.0;
+        let g=|_|0.0;
+        let lower_condition=BoundaryCondition::Neumann(1.0);
+        let upper_condition=BoundaryCondition::Dirichlet(0.0);
+        let x_min=0.0;
+        let x_max=1.0;
+        let n=100;
+        let expected_fnc=|x:f64|x-1.0;
+        let result=solve_ode(
+            &fn2, &fn1,
+            &fnc, &g, lower_condition, upper_condition,
+            x_min, x_max, n
+        );
+        let dx=compute_dx(n+2, x_min, x_max);
+        for (index, res) in result.values().iter().enumerate(){
+            assert_abs_diff_eq!(*res, expected_fnc(compute_x(index, dx, x_min)), epsilon=0.01);
+        }
+    }
 
+    #[test]
+    fn test_solve_ode_with_robin_lower(){
+        // f''(x)=0, f(0)+2*f'(0)=3, f(1)=1 => f(x)=2x-1
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|0.0;
+        let g=|_|0.0;
+        let lower_condition=BoundaryCondition::Robin{a:1.0, b:2.0, value:3.0};
+        let upper_condition=BoundaryCondition::Dirichlet(1.0);
+        let x_min=0.0;
+        let x_max=1.0;
+        let n=100;
+        let expected_fnc=|x:f64|2.0*x-1.0;
+        let result=solve_ode(
+            &fn2, &fn1,
+            &fnc, &g, lower_condition, upper_condition,
+            x_min, x_max, n
+        );
+        let dx=compute_dx(n+2, x_min, x_max);
+        for (index, res) in result.values().iter().enumerate(){
+            assert_abs_diff_eq!(*res, expected_fnc(compute_x(index, dx, x_min)), epsilon=0.01);
+        }
+    }
+
+    #[test]
+    fn test_ode_solution_xs_ys_match_eval(){
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|0.0;
+        let g=|_|1.0;
+        let lower_condition=BoundaryCondition::Dirichlet(0.0);
+        let upper_condition=BoundaryCondition::Dirichlet(0.0);
+        let result=solve_ode(
+            &fn2, &fn1,
+            &fnc, &g, lower_condition, upper_condition,
+            0.0, 1.0, 100
+        );
+        for (x, y) in result.xs().zip(result.ys()){
+            assert_abs_diff_eq!(result.eval(x), *y, epsilon=1e-9);
+        }
+    }
+
+    #[test]
+    fn test_solve_ivp(){
+        // f''(x)+f(x)=0, f(0)=0, f'(0)=1 => f(x)=sin(x)
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|1.0;
+        let g=|_|0.0;
+        let f_init=0.0;
+        let df_init=1.0;
+        let x_min=0.0;
+        let x_max=std::f64::consts::PI;
+        let result=solve_ivp(
+            &fn2, &fn1, &fnc, &g,
+            f_init, df_init,
+            x_min, x_max,
+            0.1, 1e-8, 1e-10
+        );
+        for (x, f, _) in result.iter(){
+            assert_abs_diff_eq!(*f, x.sin(), epsilon=0.0001);
+        }
+        assert_abs_diff_eq!(result.last().unwrap().0, x_max, epsilon=1e-12);
+    }
+
+    #[test]
+    fn test_solve_ivp_terminates_with_zero_atol(){
+        // f''(x)+f(x)=0, f(0)=0, f'(0)=1 => f(x)=sin(x), f'(x)=cos(x).
+        // With atol=0.0, the error scale for the f' component hits zero
+        // right where cos(x) crosses zero near x=pi/2, which must not
+        // make the adaptive step size shrink forever.
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|1.0;
+        let g=|_|0.0;
+        let x_min=0.0;
+        let x_max=std::f64::consts::PI;
+        let result=solve_ivp(
+            &fn2, &fn1, &fnc, &g,
+            0.0, 1.0,
+            x_min, x_max,
+            0.1, 1e-6, 0.0
+        );
+        assert_abs_diff_eq!(result.last().unwrap().0, x_max, epsilon=1e-9);
+    }
+
+    #[test]
+    fn test_solve_ivp_terminates_on_identically_zero_solution(){
+        // f''(x)=0, f(0)=0, f'(0)=0 => f(x)=0 everywhere, so with
+        // atol=0.0 every component's error scale and embedded-pair
+        // difference are both exactly zero, making the raw error
+        // estimate NaN (0/0). That must be treated as an exact step
+        // rather than left to shrink `h` toward `h_min` forever.
+        let fn2=|_|1.0;
+        let fn1=|_|0.0;
+        let fnc=|_|1.0;
+        let g=|_|0.0;
+        let x_min=0.0;
+        let x_max=1.0;
+        let result=solve_ivp(
+            &fn2, &fn1, &fnc, &g,
+            0.0, 0.0,
+            x_min, x_max,
+            0.1, 1e-6, 0.0
+        );
+        assert_abs_diff_eq!(result.last().unwrap().0, x_max, epsilon=1e-9);
+        for (_, f, df) in result.iter(){
+            assert_abs_diff_eq!(*f, 0.0, epsilon=1e-12);
+            assert_abs_diff_eq!(*df, 0.0, epsilon=1e-12);
+        }
+    }
+
+    #[test]
+    fn test_rk45_error_reports_unbounded_error_for_a_genuine_blow_up(){
+        // f''=k^2*f is wildly unstable; with a large enough k and step,
+        // a single rk45_step from a moderate state overflows both
+        // embedded estimates to infinity. The resulting Inf-Inf
+        // difference must be reported as unbounded error (so it keeps
+        // shrinking h toward h_min), not mistaken for the exact 0/0
+        // degenerate-tolerance case atol=0.0 alone produces.
+        let fn2=|_:f64|1.0;
+        let fn1=|_:f64|0.0;
+        let fnc=|_:f64|-1e140;
+        let g=|_:f64|0.0;
+        let (y5, y4)=rk45_step(&fn2, &fn1, &fnc, &g, 0.0, [1.0, 0.0], 10.0);
+        assert!(y5[0].is_infinite() || y5[0].is_nan());
+        let err=rk45_error(y5, y4, 0.0, 1e-6);
+        assert!(err.is_infinite(), "expected unbounded error for a genuine blow-up, got {:?}", err);
+    }
+
+    #[test]
+    fn test_solve_ode_system_matches_scalar(){
+        // A decoupled 2x2 system where each component is the same scalar
+        // problem as `test_solve_ode_f64`, so the block solver must agree
+        // with the scalar one component-by-component.
+        let a=|_:f64|vec![vec![1.5, 0.0], vec![0.0, 1.5]];
+        let b=|_:f64|vec![vec![5.0, 0.0], vec![0.0, 5.0]];
+        let c=|_:f64|vec![vec![1.5, 0.0], vec![0.0, 1.5]];
+        let g=|_:f64|vec![0.0, 0.0];
+        let x_min=0.0;
+        let x_max=1.0;
+        let n=100;
+        let expected_fnc=|x:f64|{
+            let coef1=-1.0/3.0;
+            let coef2=-3.0;
             let c2=1.0/(coef1.exp()-coef2.exp());
             let c1=-c2;
             c1*(coef2*x).exp()+c2*(coef1*x).exp()
         };
-        let result=solve_ode(
-            &fn2, &fn1, 
-            &fnc, init_cond_lower, init_cond_upper, 
+        let result=solve_ode_system(
+            &a, &b, &c, &g,
+            vec![0.0, 0.0], vec![1.0, 1.0],
             x_min, x_max, n
+        ).unwrap();
+        let dx=compute_dx(n+2, x_min, x_max);
+        for (offset, row) in result.iter().enumerate(){
+            let expected=expected_fnc(compute_x(offset+1, dx, x_min));
+            for value in row {
+                assert_abs_diff_eq!(*value, expected, epsilon=0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_ode_system_singular_pivot_errors(){
+        // All coefficients are zero, so every pivot block is the zero
+        // matrix and the first forward-sweep factorization must fail.
+        let a=|_:f64|vec![vec![0.0]];
+        let b=|_:f64|vec![vec![0.0]];
+        let c=|_:f64|vec![vec![0.0]];
+        let g=|_:f64|vec![0.0];
+        let result=solve_ode_system(
+            &a, &b, &c, &g,
+            vec![0.0], vec![0.0],
+            0.0, 1.0, 10
         );
+        assert_eq!(result, Err(OdeSystemError::SingularPivot{node_index:0}));
+    }
+
+    #[test]
+    fn test_solve_ode_system_with_source(){
+        // A decoupled 2x2 system where each component is the same forced
+        // problem as `test_solve_ode_with_source`: f''(x)=1, f(0)=0,
+        // f(1)=0 => f(x)=(x^2-x)/2.
+        let a=|_:f64|vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let b=|_:f64|vec![vec![0.0, 0.0], vec![0.0, 0.0]];
+        let c=|_:f64|vec![vec![0.0, 0.0], vec![0.0, 0.0]];
+        let g=|_:f64|vec![1.0, 1.0];
+        let x_min=0.0;
+        let x_max=1.0;
+        let n=100;
+        let expected_fnc=|x:f64|(x.powi(2)-x)/2.0;
+        let result=solve_ode_system(
+            &a, &b, &c, &g,
+            vec![0.0, 0.0], vec![0.0, 0.0],
+            x_min, x_max, n
+        ).unwrap();
         let dx=compute_dx(n+2, x_min, x_max);
-        for (index, res) in result.iter().enumerate(){
-            assert_abs_diff_eq!(*res, expected_fnc(compute_x(index+1, dx, x_min)), epsilon=0.001);
+        for (offset, row) in result.iter().enumerate(){
+            let expected=expected_fnc(compute_x(offset+1, dx, x_min));
+            for value in row {
+                assert_abs_diff_eq!(*value, expected, epsilon=0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_ode_system_single_unknown_node(){
+        // Same problem as `test_solve_ode_single_unknown_node`, carried
+        // through a decoupled 2x2 system: f''(x)=0, f(0)=0, f(1)=1 =>
+        // f(x)=x, with a single interior node that has no lower or
+        // upper neighbor at all.
+        let a=|_:f64|vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let b=|_:f64|vec![vec![0.0, 0.0], vec![0.0, 0.0]];
+        let c=|_:f64|vec![vec![0.0, 0.0], vec![0.0, 0.0]];
+        let g=|_:f64|vec![0.0, 0.0];
+        let result=solve_ode_system(
+            &a, &b, &c, &g,
+            vec![0.0, 0.0], vec![1.0, 1.0],
+            0.0, 1.0, 1
+        ).unwrap();
+        for row in &result {
+            for value in row {
+                assert_abs_diff_eq!(*value, 0.5, epsilon=1e-9);
+            }
         }
     }
 }